@@ -1,7 +1,11 @@
+mod hashlife;
+mod rule;
 mod utils;
 
 use bit_vec::BitVec;
-use parsers::parsers::parse_rle_string;
+use hashlife::{level_of, HashLifeEngine};
+use parsers::parsers::{encode_rle_body, parse_rle_file, parse_rle_string, RleError};
+use rule::Rule;
 use wasm_bindgen::prelude::*;
 
 
@@ -24,6 +28,27 @@ pub struct Universe {
     width: u32,
     height: u32,
     cells: BitVec,
+    rule: Rule,
+    boundary: Boundary,
+    last_born: Vec<u32>,
+    last_died: Vec<u32>,
+}
+
+/// Determines how `live_neighbor_count` treats the edges of the grid.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// The grid wraps: the left/right and top/bottom edges are adjacent.
+    Toroidal,
+    /// The grid has fixed edges: neighbors outside `[0, width) x [0, height)`
+    /// are treated as dead instead of wrapping.
+    Bounded,
+}
+
+impl Default for Boundary {
+    fn default() -> Self {
+        Boundary::Toroidal
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -32,15 +57,18 @@ pub struct Shape {
 }
 
 impl Shape {
-    pub fn from_rle_string(rle_string: &str) -> Self {
+    pub fn from_rle_string(rle_string: &str) -> Result<Self, RleError> {
+        let (_, atoms) = parse_rle_string(rle_string)
+            .map_err(|e| RleError::MalformedBody(e.to_string()))?;
+
         let mut offset = (0, 0);
         let mut alive_cells = vec![];
 
-        for atom in parse_rle_string(rle_string).unwrap().1 {
+        for atom in atoms {
             atom.grow_pattern(&mut offset, &mut alive_cells)
         }
 
-        Self { alive_cells }
+        Ok(Self { alive_cells })
     }
 
     pub fn shift(&mut self, shift: (u32, u32)) {
@@ -49,6 +77,23 @@ impl Shape {
             cell.1 = cell.1 + shift.1;
         }
     }
+
+    /// Checks that every alive cell falls within a `width` x `height` grid.
+    pub fn validate_bounds(&self, width: u32, height: u32) -> Result<(), RleError> {
+        for &(x, y) in &self.alive_cells {
+            if x >= width || y >= height {
+                return Err(RleError::OutOfBounds { x, y, width, height });
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes this shape to `.rle` text, trimmed to the minimal bounding
+    /// box that contains its alive cells.
+    pub fn to_rle(&self) -> String {
+        let (width, height, body) = encode_rle_body(&self.alive_cells);
+        format!("x = {}, y = {}\n{}!", width, height, body)
+    }
 }
 
 impl Universe {
@@ -65,21 +110,85 @@ impl Universe {
         }
     }
 
+    fn alive_cell_coords(&self) -> Vec<(u32, u32)> {
+        let mut alive_cells = vec![];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                if self.cells.get(self.get_index(row, col)).unwrap() {
+                    alive_cells.push((row, col));
+                }
+            }
+        }
+        alive_cells
+    }
+
     fn get_index(&self, row: u32, column: u32) -> usize {
         (row * self.width + column) as usize
     }
 
+    /// Advances the grid by one generation, returning the flat `row*width +
+    /// column` indices of every cell that was born or died, so callers that
+    /// want a delta don't have to diff the grid themselves.
+    fn advance(&mut self) -> (Vec<u32>, Vec<u32>) {
+        let mut next = self.cells.clone();
+        let mut born = Vec::new();
+        let mut died = Vec::new();
+
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let cell = self.cells.get(idx).unwrap();
+                let live_neighbor_count = self.live_neighbor_count(row, col) as usize;
+
+                let next_cell = if cell {
+                    self.rule.survival[live_neighbor_count]
+                } else {
+                    self.rule.birth[live_neighbor_count]
+                };
+
+                if next_cell && !cell {
+                    born.push(idx as u32);
+                } else if !next_cell && cell {
+                    died.push(idx as u32);
+                }
+
+                next.set(idx, next_cell);
+            }
+        }
+
+        self.cells = next;
+        (born, died)
+    }
+
     fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
         let mut count = 0;
-        for delta_row in [self.height - 1, 0, 1].iter().cloned() {
-            for delta_col in [self.width - 1, 0, 1].iter().cloned() {
+        for delta_row in [-1i32, 0, 1].iter().cloned() {
+            for delta_col in [-1i32, 0, 1].iter().cloned() {
                 if delta_row == 0 && delta_col == 0 {
                     continue;
                 }
 
-                let neighbor_row = (row + delta_row) % self.height;
-                let neighbor_col = (column + delta_col) % self.width;
-                let idx = self.get_index(neighbor_row, neighbor_col);
+                let neighbor_row = row as i32 + delta_row;
+                let neighbor_col = column as i32 + delta_col;
+
+                let (neighbor_row, neighbor_col) = match self.boundary {
+                    Boundary::Toroidal => (
+                        neighbor_row.rem_euclid(self.height as i32),
+                        neighbor_col.rem_euclid(self.width as i32),
+                    ),
+                    Boundary::Bounded => {
+                        if neighbor_row < 0
+                            || neighbor_row >= self.height as i32
+                            || neighbor_col < 0
+                            || neighbor_col >= self.width as i32
+                        {
+                            continue;
+                        }
+                        (neighbor_row, neighbor_col)
+                    }
+                };
+
+                let idx = self.get_index(neighbor_row as u32, neighbor_col as u32);
                 if self.cells.get(idx).unwrap() {
                     count += 1;
                 }
@@ -98,9 +207,47 @@ impl Universe {
             height,
             width,
             cells: BitVec::from_elem((width * height) as usize, false),
+            rule: Rule::default(),
+            boundary: Boundary::default(),
+            last_born: Vec::new(),
+            last_died: Vec::new(),
         }
     }
 
+    /// Sets whether the grid wraps at its edges (`Toroidal`) or treats
+    /// out-of-bounds neighbors as dead (`Bounded`).
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Sets the birth/survival rule from a Life-like notation string such as
+    /// `"B3/S23"` (Conway's Life) or `"B36/S23"` (HighLife).
+    pub fn set_rule(&mut self, rule_str: &str) -> Result<(), JsValue> {
+        self.rule = Rule::parse(rule_str).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// Loads a complete `.rle` file, resizing the universe to its declared
+    /// dimensions and adopting its rule, if one is declared.
+    pub fn load_rle_file(&mut self, rle_file_string: &str) -> Result<(), JsValue> {
+        let file =
+            parse_rle_file(rle_file_string).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        self.set_width(file.width);
+        self.set_height(file.height);
+        if let Some(rule_str) = &file.rule {
+            self.set_rule(rule_str)?;
+        }
+        // RLE cells are `(x, y)`, i.e. `(column, row)`; `parse_rle_file` has
+        // already checked them against the declared dimensions.
+        for (x, y) in file.shape.alive_cells {
+            let idx = self.get_index(y, x);
+            self.cells.set(idx, true);
+        }
+
+        Ok(())
+    }
+
     pub fn set_width(&mut self, width: u32) {
         self.width = width;
         self.cells = BitVec::from_elem((width * self.height) as usize, false);
@@ -111,47 +258,117 @@ impl Universe {
         self.cells = BitVec::from_elem((self.width * height) as usize, false);
     }
 
-    pub fn set_rle_shape(&mut self, rle_string: &str, x: u32, y: u32) {
-        let mut shape = Shape::from_rle_string(rle_string);
+    pub fn set_rle_shape(&mut self, rle_string: &str, x: u32, y: u32) -> Result<(), JsValue> {
+        let mut shape =
+            Shape::from_rle_string(rle_string).map_err(|e| JsValue::from_str(&e.to_string()))?;
         shape.shift((x, y));
         self.set_cells(shape.alive_cells);
+        Ok(())
     }
 
     pub fn tick(&mut self) {
-        let mut next = self.cells.clone();
+        self.advance();
+    }
 
-        for row in 0..self.height {
-            for col in 0..self.width {
-                let idx = self.get_index(row, col);
-                let cell = self.cells.get(idx).unwrap();
-                let live_neighbor_count = self.live_neighbor_count(row, col);
-
-                let next_cell = match (cell, live_neighbor_count) {
-                    // Rule 1: Any live cell with fewer than two live neighbors
-                    // dies, as if caused by underpopulation.
-                    (true, x) if x < 2 => false,
-                    // Rule 2: Any live cell with two or three live neighbors
-                    // lives on to the next generation.
-                    (true, 2) | (true, 3) => true,
-                    // Rule 3: Any live cell with more than three live
-                    // neighbors dies, as if from overpopulation.
-                    (true, x) if x > 3 => false,
-                    // Rule 4: Any dead cell with exactly three live neighbors
-                    // becomes a live cell, as if by reproduction.
-                    (false, 3) => true,
-                    // all other cells remain in the same state.
-                    (otherwise, _) => otherwise,
-                };
+    /// Like `tick`, but also records which cells flipped, so `born_cells`
+    /// and `died_cells` can report just the changed indices instead of
+    /// forcing the caller to re-scan the whole grid to find them.
+    pub fn tick_with_delta(&mut self) {
+        let (born, died) = self.advance();
+        self.last_born = born;
+        self.last_died = died;
+    }
 
-                next.set(idx, next_cell);
-            }
+    /// Flat `row*width + column` indices of the cells born on the most
+    /// recent `tick_with_delta`.
+    pub fn born_cells(&self) -> Vec<u32> {
+        self.last_born.clone()
+    }
+
+    /// Flat `row*width + column` indices of the cells that died on the most
+    /// recent `tick_with_delta`.
+    pub fn died_cells(&self) -> Vec<u32> {
+        self.last_died.clone()
+    }
+
+    /// Flat, alternating `row, column` pairs for every currently-alive cell
+    /// (unlike `born_cells`/`died_cells`, which return single flat
+    /// `row*width + column` indices).
+    pub fn live_cells(&self) -> Vec<u32> {
+        let mut flat = Vec::new();
+        for (row, col) in self.alive_cell_coords() {
+            flat.push(row);
+            flat.push(col);
         }
-        self.cells = next;
+        flat
     }
 
     pub fn render(&self) -> String {
         self.to_string()
     }
+
+    /// Advances the universe by `2^n` generations using the Hashlife
+    /// quadtree engine instead of `2^n` linear calls to `tick`, which pays off
+    /// on large, sparse, or periodic patterns. The universe is padded with
+    /// just enough dead border for a single Hashlife step of this size;
+    /// callers advancing far beyond the grid's own size in one call should
+    /// expect activity that would have grown past the border to be clipped.
+    ///
+    /// The Hashlife engine itself has no notion of wraparound - everything
+    /// outside the padded macrocell is dead - so this only supports
+    /// `Boundary::Bounded`; calling it on a `Boundary::Toroidal` universe
+    /// would silently diverge from what the same number of `tick`s produce
+    /// and is rejected instead.
+    pub fn step_pow2(&mut self, n: u8) -> Result<(), JsValue> {
+        if self.boundary == Boundary::Toroidal {
+            return Err(JsValue::from_str(
+                "step_pow2 only supports Boundary::Bounded; the hashlife engine treats everything outside its padded region as dead, which would silently diverge from Boundary::Toroidal's wraparound",
+            ));
+        }
+
+        if n > 31 {
+            return Err(JsValue::from_str(
+                "n is too large; step_pow2 supports at most 2^31 generations per call",
+            ));
+        }
+        let target_level = n + 2;
+
+        let engine = HashLifeEngine::new(self.rule);
+        let node = engine.from_bitvec(&self.cells, self.width, self.height);
+        let base_level = level_of(&node);
+
+        // `result` keeps only the centre half of its node, so the grid needs
+        // at least one level of padding to come back whole.
+        if base_level >= target_level {
+            return Err(JsValue::from_str(
+                "universe is too large for this many generations in one hashlife step; pick a larger n",
+            ));
+        }
+
+        let padded = engine.pad_to_level(&node, target_level);
+        let advanced = engine.step_pow2(&padded, n);
+
+        let shift = (1i64 << target_level.saturating_sub(2)) - (1i64 << base_level.saturating_sub(1));
+        self.cells = engine.to_bitvec(&advanced, self.width, self.height, shift);
+        Ok(())
+    }
+
+    /// Serializes the live cells to `.rle` text, trimmed to their minimal
+    /// bounding box and tagged with this universe's current rule.
+    pub fn to_rle(&self) -> String {
+        // `encode_rle_body`/the RLE convention treat the first tuple element
+        // as x (column) and the second as y (row), but `alive_cell_coords`
+        // returns `(row, col)` - flip them here instead of there, since
+        // `live_cells`/`born_cells`/`died_cells` want the `(row, col)`
+        // convention.
+        let xy_cells: Vec<(u32, u32)> = self
+            .alive_cell_coords()
+            .into_iter()
+            .map(|(row, col)| (col, row))
+            .collect();
+        let (width, height, body) = encode_rle_body(&xy_cells);
+        format!("x = {}, y = {}, rule = {}\n{}!", width, height, self.rule, body)
+    }
 }
 
 use std::fmt;
@@ -175,7 +392,7 @@ impl fmt::Display for Universe {
 
 #[cfg(test)]
 mod tests {
-    use crate::{ Shape, Universe};
+    use crate::{ parsers::parsers::{parse_rle_file, RleError}, Boundary, Shape, Universe};
 
     #[test]
     fn blank_universe_works() {
@@ -195,6 +412,94 @@ mod tests {
         assert_eq!(u.cells[3], true);
     }
 
+    #[test]
+    fn bounded_field_blocks_wraparound_a_torus_would_allow() {
+        // On a torus, the corner (0, 0) sees every other cell in a 3x3 grid
+        // as a neighbor, so three live cells scattered around the grid can
+        // wrap around and feed its birth. In a bounded field, the same
+        // corner only has 3 real neighbors, so the wrapped-in cell can't
+        // contribute - this is the same mechanism that lets a glider fly off
+        // a bounded field's edge instead of reappearing on the far side.
+        let live_cells = vec![(1, 0), (0, 1), (2, 2)];
+
+        let mut torus = Universe::new(3, 3);
+        torus.set_cells(live_cells.clone());
+        torus.tick();
+        assert_eq!(torus.cells[torus.get_index(0, 0)], true);
+
+        let mut bounded = Universe::new(3, 3);
+        bounded.set_boundary(Boundary::Bounded);
+        bounded.set_cells(live_cells);
+        bounded.tick();
+        assert_eq!(bounded.cells[bounded.get_index(0, 0)], false);
+    }
+
+    #[test]
+    fn tick_does_not_underflow_on_a_single_cell_grid() {
+        let mut u = Universe::new(1, 1);
+        u.set_boundary(Boundary::Bounded);
+        u.tick();
+        assert_eq!(u.cells[0], false);
+    }
+
+    #[test]
+    fn tick_respects_configured_rule() {
+        let mut u = Universe::new(3, 3);
+        u.set_cells(vec![(0, 0)]);
+        u.set_rule("B1/S").unwrap();
+        u.tick();
+
+        // The lone cell had no live neighbors, so it dies under "S" (no
+        // survival counts); every other cell had exactly one live neighbor
+        // (the lone cell), so all of them are born under "B1".
+        for (idx, cell) in u.cells.iter().enumerate() {
+            assert_eq!(cell, idx != 0, "cell {} should flip", idx);
+        }
+    }
+
+    #[test]
+    fn step_pow2_rejects_a_toroidal_universe() {
+        let mut u = Universe::new(4, 4);
+        assert!(u.step_pow2(1).is_err());
+    }
+
+    #[test]
+    fn step_pow2_rejects_n_too_large_to_shift() {
+        let mut u = Universe::new(4, 4);
+        u.set_boundary(Boundary::Bounded);
+        assert!(u.step_pow2(63).is_err());
+    }
+
+    #[test]
+    fn tick_with_delta_reports_only_the_cells_that_flipped() {
+        let mut u = Universe::new(3, 3);
+        u.set_boundary(Boundary::Bounded);
+        u.set_cells(vec![(1, 0), (1, 1), (1, 2)]); // a blinker, bounded so it can't wrap
+
+        u.tick_with_delta();
+
+        let mut born = u.born_cells();
+        born.sort();
+        let mut died = u.died_cells();
+        died.sort();
+
+        assert_eq!(born, vec![u.get_index(0, 1) as u32, u.get_index(2, 1) as u32]);
+        assert_eq!(died, vec![u.get_index(1, 0) as u32, u.get_index(1, 2) as u32]);
+    }
+
+    #[test]
+    fn live_cells_returns_only_the_alive_coordinates() {
+        let mut u = Universe::new(2, 2);
+        u.set_cells(vec![(0, 1), (1, 0)]);
+
+        let flat = u.live_cells();
+        let mut pairs: Vec<(u32, u32)> =
+            flat.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        pairs.sort();
+
+        assert_eq!(pairs, vec![(0, 1), (1, 0)]);
+    }
+
     #[test]
     fn shifting_patterns_works() {
         let mut pattern = Shape {
@@ -207,12 +512,78 @@ mod tests {
     #[test]
     fn parsing_pattern_works() {
         assert_eq!(
-            Shape::from_rle_string("2bobo23$4b2o"),
+            Shape::from_rle_string("2bobo23$4b2o").unwrap(),
             Shape {
                 alive_cells: vec![(2, 0), (4, 0), (4, 23), (5, 23)],
             }
         );
     }
+
+    #[test]
+    fn parsing_malformed_pattern_errors_instead_of_panicking() {
+        assert!(Shape::from_rle_string("not rle").is_err());
+    }
+
+    #[test]
+    fn shape_to_rle_round_trips_through_the_trimmed_bounding_box() {
+        let shape = Shape::from_rle_string("2bobo23$4b2o").unwrap();
+        let reparsed = parse_rle_file(&shape.to_rle()).unwrap();
+
+        let min_x = shape.alive_cells.iter().map(|&(x, _)| x).min().unwrap();
+        let min_y = shape.alive_cells.iter().map(|&(_, y)| y).min().unwrap();
+        let mut trimmed = shape.clone();
+        for cell in &mut trimmed.alive_cells {
+            cell.0 -= min_x;
+            cell.1 -= min_y;
+        }
+
+        assert_eq!(reparsed.shape, trimmed);
+    }
+
+    #[test]
+    fn universe_to_rle_includes_its_rule() {
+        let mut u = Universe::new(4, 4);
+        u.set_cells(vec![(1, 1), (1, 2), (2, 1)]);
+        u.set_rule("B36/S23").unwrap();
+
+        let rle_text = u.to_rle();
+        let file = parse_rle_file(&rle_text).unwrap();
+
+        assert_eq!(file.rule, Some("B36/S23".to_string()));
+        assert_eq!(file.width, 2);
+        assert_eq!(file.height, 2);
+    }
+
+    #[test]
+    fn universe_to_rle_does_not_transpose_a_non_square_pattern() {
+        // A 1-row x 4-column universe with two live cells in that row, 3
+        // columns apart: wide and flat, and asymmetric under transposition,
+        // so swapping row/col would be caught here.
+        let mut u = Universe::new(1, 4);
+        // Set the cells directly: `set_cells` reduces its second coordinate
+        // modulo the height, so it can't reach column 3 of a 1-row grid.
+        let (first, last) = (u.get_index(0, 0), u.get_index(0, 3));
+        u.cells.set(first, true);
+        u.cells.set(last, true);
+
+        let file = parse_rle_file(&u.to_rle()).unwrap();
+
+        assert_eq!(file.width, 4);
+        assert_eq!(file.height, 1);
+        assert_eq!(
+            file.shape,
+            Shape { alive_cells: vec![(0, 0), (3, 0)] }
+        );
+    }
+
+    #[test]
+    fn shape_out_of_declared_bounds_is_rejected() {
+        let file = parse_rle_file("x = 2, y = 2\n3o!");
+        assert_eq!(
+            file,
+            Err(RleError::OutOfBounds { x: 2, y: 0, width: 2, height: 2 })
+        );
+    }
 }
 
 pub(self) mod parsers;
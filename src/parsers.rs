@@ -1,5 +1,182 @@
 pub mod parsers {
+    use crate::Shape;
     use nom::IResult;
+    use std::fmt;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum RleError {
+        InvalidHeader(String),
+        MalformedBody(String),
+        MissingTerminator,
+        OutOfBounds { x: u32, y: u32, width: u32, height: u32 },
+    }
+
+    impl fmt::Display for RleError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RleError::InvalidHeader(line) => write!(f, "invalid RLE header: {}", line),
+                RleError::MalformedBody(reason) => write!(f, "malformed RLE body: {}", reason),
+                RleError::MissingTerminator => write!(f, "RLE body is missing its `!` terminator"),
+                RleError::OutOfBounds { x, y, width, height } => write!(
+                    f,
+                    "cell ({}, {}) lies outside the declared {}x{} bounds",
+                    x, y, width, height
+                ),
+            }
+        }
+    }
+
+    impl std::error::Error for RleError {}
+
+    /// A fully parsed `.rle` file: its declared dimensions and rule, any
+    /// comment lines, and the `Shape` decoded from the run-length body.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RleFile {
+        pub width: u32,
+        pub height: u32,
+        pub rule: Option<String>,
+        pub comments: Vec<String>,
+        pub shape: Shape,
+    }
+
+    /// Parses a complete `.rle` file: leading `#`-prefixed comment lines, the
+    /// `x = m, y = n, rule = ...` header, and the run-length body up to its
+    /// `!` terminator.
+    pub fn parse_rle_file(input: &str) -> Result<RleFile, RleError> {
+        let mut comments = Vec::new();
+        let mut header = None;
+        let mut body_lines: Vec<&str> = Vec::new();
+        let mut in_body = false;
+
+        for line in input.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if in_body {
+                body_lines.push(trimmed);
+                continue;
+            }
+            if trimmed.starts_with('#') {
+                comments.push(trimmed.to_string());
+                continue;
+            }
+            if trimmed.starts_with('x') {
+                header = Some(parse_header_line(trimmed)?);
+                in_body = true;
+                continue;
+            }
+            in_body = true;
+            body_lines.push(trimmed);
+        }
+
+        let (width, height, rule) =
+            header.ok_or_else(|| RleError::InvalidHeader("missing x/y header".to_string()))?;
+
+        let joined_body = body_lines.concat();
+        let body = match joined_body.find('!') {
+            Some(idx) => &joined_body[..idx],
+            None => return Err(RleError::MissingTerminator),
+        };
+
+        let shape = Shape::from_rle_string(body)?;
+        shape.validate_bounds(width, height)?;
+
+        Ok(RleFile { width, height, rule, comments, shape })
+    }
+
+    fn parse_header_line(line: &str) -> Result<(u32, u32, Option<String>), RleError> {
+        let mut width = None;
+        let mut height = None;
+        let mut rule = None;
+
+        for field in line.split(',') {
+            let mut parts = field.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = parts.next().unwrap_or("").trim();
+            match key {
+                "x" => width = value.parse::<u32>().ok(),
+                "y" => height = value.parse::<u32>().ok(),
+                "rule" => rule = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        match (width, height) {
+            (Some(w), Some(h)) => Ok((w, h, rule)),
+            _ => Err(RleError::InvalidHeader(line.to_string())),
+        }
+    }
+
+    /// Run-length-encodes a set of alive `(x, y)` cells into the minimal
+    /// bounding box that contains them, returning its width, height, and the
+    /// `b`/`o`/`$` body (without the trailing `!` terminator).
+    pub fn encode_rle_body(alive_cells: &[(u32, u32)]) -> (u32, u32, String) {
+        if alive_cells.is_empty() {
+            return (0, 0, String::new());
+        }
+
+        let min_x = alive_cells.iter().map(|&(x, _)| x).min().unwrap();
+        let max_x = alive_cells.iter().map(|&(x, _)| x).max().unwrap();
+        let min_y = alive_cells.iter().map(|&(_, y)| y).min().unwrap();
+        let max_y = alive_cells.iter().map(|&(_, y)| y).max().unwrap();
+
+        let width = max_x - min_x + 1;
+        let height = max_y - min_y + 1;
+
+        let mut grid = vec![vec![false; width as usize]; height as usize];
+        for &(x, y) in alive_cells {
+            grid[(y - min_y) as usize][(x - min_x) as usize] = true;
+        }
+
+        let mut body = String::new();
+        let mut last_content_row: Option<usize> = None;
+        for (i, row) in grid.iter().enumerate() {
+            let tokens = encode_rle_row(row);
+            if tokens.is_empty() {
+                // Entirely-dead rows contribute nothing of their own; they
+                // just widen the `$` gap to the next row with content.
+                continue;
+            }
+
+            if let Some(last) = last_content_row {
+                let gap = i - last;
+                if gap == 1 {
+                    body.push('$');
+                } else {
+                    body.push_str(&format!("{}$", gap));
+                }
+            }
+
+            body.push_str(&tokens);
+            last_content_row = Some(i);
+        }
+
+        (width, height, body)
+    }
+
+    fn encode_rle_row(row: &[bool]) -> String {
+        let last_alive = row.iter().rposition(|&alive| alive);
+        let effective_len = last_alive.map_or(0, |idx| idx + 1);
+
+        let mut tokens = String::new();
+        let mut i = 0;
+        while i < effective_len {
+            let alive = row[i];
+            let start = i;
+            while i < effective_len && row[i] == alive {
+                i += 1;
+            }
+            let run = i - start;
+            let symbol = if alive { 'o' } else { 'b' };
+            if run == 1 {
+                tokens.push(symbol);
+            } else {
+                tokens.push_str(&format!("{}{}", run, symbol));
+            }
+        }
+        tokens
+    }
 
     #[derive(Debug, Eq, PartialEq, Clone)]
     pub enum RleSymbol {
@@ -94,5 +271,67 @@ pub mod parsers {
             assert_eq!(offset, (2, 1));
             assert_eq!(pattern, vec![(0, 1), (1, 1)]);
         }
+
+        #[test]
+        fn parsing_rle_file_with_comments_and_header_works() {
+            let file = parse_rle_file(
+                "#N Glider\n#C A simple spaceship\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!",
+            )
+            .unwrap();
+
+            assert_eq!(file.width, 3);
+            assert_eq!(file.height, 3);
+            assert_eq!(file.rule, Some("B3/S23".to_string()));
+            assert_eq!(file.comments, vec!["#N Glider", "#C A simple spaceship"]);
+            assert_eq!(
+                file.shape,
+                Shape {
+                    alive_cells: vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+                }
+            );
+        }
+
+        #[test]
+        fn parsing_rle_file_without_rule_defaults_to_none() {
+            let file = parse_rle_file("x = 1, y = 1\no!").unwrap();
+            assert_eq!(file.rule, None);
+        }
+
+        #[test]
+        fn parsing_rle_file_missing_terminator_errors() {
+            assert_eq!(
+                parse_rle_file("x = 1, y = 1\no"),
+                Err(RleError::MissingTerminator)
+            );
+        }
+
+        #[test]
+        fn parsing_rle_file_missing_header_errors() {
+            assert_eq!(
+                parse_rle_file("bo!"),
+                Err(RleError::InvalidHeader("missing x/y header".to_string()))
+            );
+        }
+
+        #[test]
+        fn encode_rle_body_trims_to_the_minimal_bounding_box() {
+            let (width, height, body) = encode_rle_body(&[(2, 0), (4, 0), (4, 23), (5, 23)]);
+            assert_eq!(width, 4);
+            assert_eq!(height, 24);
+            assert_eq!(body, "obo23$2b2o");
+        }
+
+        #[test]
+        fn encode_rle_body_of_empty_shape_is_empty() {
+            assert_eq!(encode_rle_body(&[]), (0, 0, String::new()));
+        }
+
+        #[test]
+        fn parsing_rle_file_out_of_bounds_cell_errors() {
+            assert_eq!(
+                parse_rle_file("x = 1, y = 1\n3o!"),
+                Err(RleError::OutOfBounds { x: 1, y: 0, width: 1, height: 1 })
+            );
+        }
     }
 }
@@ -0,0 +1,142 @@
+use std::fmt;
+
+/// Birth/survival lookup tables for a Life-like cellular automaton rule,
+/// indexed by live-neighbor count (0-8).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: [bool; 9],
+    pub survival: [bool; 9],
+}
+
+impl Rule {
+    pub fn conways_life() -> Self {
+        Self::parse("B3/S23").unwrap()
+    }
+
+    /// Parses a Life-like rule string such as `"B36/S23"` (HighLife) or the
+    /// bare `"36/23"` spelling. Digits may appear in any order.
+    pub fn parse(rule_str: &str) -> Result<Self, RuleError> {
+        let rule_str = rule_str.trim();
+        let (birth_part, survival_part) = rule_str
+            .split_once('/')
+            .ok_or_else(|| RuleError::InvalidFormat(rule_str.to_string()))?;
+
+        let birth_digits = birth_part.strip_prefix('B').unwrap_or(birth_part);
+        let survival_digits = survival_part.strip_prefix('S').unwrap_or(survival_part);
+
+        Ok(Rule {
+            birth: digits_to_table(birth_digits)?,
+            survival: digits_to_table(survival_digits)?,
+        })
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conways_life()
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Renders the rule back into `Bxx/Syy` notation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        for n in 0..9 {
+            if self.birth[n] {
+                write!(f, "{}", n)?;
+            }
+        }
+        write!(f, "/S")?;
+        for n in 0..9 {
+            if self.survival[n] {
+                write!(f, "{}", n)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn digits_to_table(digits: &str) -> Result<[bool; 9], RuleError> {
+    let mut table = [false; 9];
+    for ch in digits.chars() {
+        let n = ch.to_digit(10).ok_or(RuleError::InvalidDigit(ch))?;
+        if n > 8 {
+            return Err(RuleError::DigitOutOfRange(n));
+        }
+        table[n as usize] = true;
+    }
+    Ok(table)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    InvalidFormat(String),
+    InvalidDigit(char),
+    DigitOutOfRange(u32),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::InvalidFormat(s) => write!(f, "invalid rule string: {}", s),
+            RuleError::InvalidDigit(c) => write!(f, "invalid digit in rule string: {}", c),
+            RuleError::DigitOutOfRange(n) => write!(f, "neighbor count {} is out of range 0-8", n),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_conways_life() {
+        let rule = Rule::parse("B3/S23").unwrap();
+        assert_eq!(rule, Rule::conways_life());
+        assert_eq!(rule.birth[3], true);
+        assert_eq!(rule.survival[2], true);
+        assert_eq!(rule.survival[3], true);
+        assert_eq!(rule.birth[2], false);
+    }
+
+    #[test]
+    fn parses_highlife() {
+        let rule = Rule::parse("B36/S23").unwrap();
+        assert_eq!(rule.birth[3], true);
+        assert_eq!(rule.birth[6], true);
+        assert_eq!(rule.birth[2], false);
+    }
+
+    #[test]
+    fn parses_bare_digit_spelling() {
+        let rule = Rule::parse("36/23").unwrap();
+        assert_eq!(rule, Rule::parse("B36/S23").unwrap());
+    }
+
+    #[test]
+    fn parses_seeds_with_empty_survival() {
+        let rule = Rule::parse("B2/S").unwrap();
+        assert_eq!(rule.birth[2], true);
+        assert_eq!(rule.survival, [false; 9]);
+    }
+
+    #[test]
+    fn rejects_missing_slash() {
+        assert_eq!(
+            Rule::parse("B3S23"),
+            Err(RuleError::InvalidFormat("B3S23".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_digit() {
+        assert_eq!(Rule::parse("B9/S23"), Err(RuleError::DigitOutOfRange(9)));
+    }
+
+    #[test]
+    fn displays_as_b_s_notation() {
+        assert_eq!(Rule::parse("B36/S23").unwrap().to_string(), "B36/S23");
+    }
+}